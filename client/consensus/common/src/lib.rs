@@ -36,7 +36,11 @@ use polkadot_primitives::v1::{
 use codec::Decode;
 use futures::{future, select, FutureExt, Stream, StreamExt};
 
-use std::{marker::PhantomData, sync::Arc};
+use std::{
+	marker::PhantomData,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 /// Errors that can occur while following the polkadot relay-chain.
 #[derive(Debug)]
@@ -561,6 +565,133 @@ impl<B: BlockT> ParachainConsensus<B> for Box<dyn ParachainConsensus<B> + Send +
 	}
 }
 
+/// A [`ParachainConsensus`] that retries candidate production of the wrapped consensus.
+///
+/// [`ParachainConsensus::produce_candidate`] returns `None` when it fails, so a single transient
+/// error would cost the whole relay chain block. This wrapper calls the inner consensus again, up
+/// to `max_retries` times and waiting `retry_delay` in between, until it returns a candidate.
+///
+/// As the inner consensus also returns `None` when it decided to not build a candidate, retrying
+/// could produce a candidate for a relay parent that is already stale. No retry is started if it
+/// could not begin before `deadline` has passed since the first attempt.
+#[derive(Clone)]
+pub struct RetryingConsensus<C> {
+	inner: C,
+	max_retries: u32,
+	retry_delay: Duration,
+	deadline: Duration,
+}
+
+impl<C> RetryingConsensus<C> {
+	/// Create new instance of `Self`.
+	///
+	/// - `inner`: The consensus implementation that produces the candidates.
+	/// - `max_retries`: The number of times to retry after the first attempt failed.
+	/// - `retry_delay`: The time to wait before each retry.
+	/// - `deadline`: The time after the first attempt, after which no retry is started.
+	pub fn new(inner: C, max_retries: u32, retry_delay: Duration, deadline: Duration) -> Self {
+		Self {
+			inner,
+			max_retries,
+			retry_delay,
+			deadline,
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl<B, C> ParachainConsensus<B> for RetryingConsensus<C>
+where
+	B: BlockT,
+	C: ParachainConsensus<B> + Clone,
+{
+	async fn produce_candidate(
+		&mut self,
+		parent: &B::Header,
+		relay_parent: PHash,
+		validation_data: &PersistedValidationData,
+	) -> Option<ParachainCandidate<B>> {
+		let started = Instant::now();
+		let mut retries = 0;
+
+		loop {
+			let candidate = self
+				.inner
+				.produce_candidate(parent, relay_parent, validation_data)
+				.await;
+
+			if candidate.is_some() || retries >= self.max_retries {
+				return candidate;
+			}
+
+			if started.elapsed() + self.retry_delay >= self.deadline {
+				tracing::debug!(
+					target: "cumulus-consensus",
+					?relay_parent,
+					"Producing the candidate failed, no time left to retry.",
+				);
+				return None;
+			}
+
+			retries += 1;
+			tracing::debug!(
+				target: "cumulus-consensus",
+				?relay_parent,
+				retry = retries,
+				"Producing the candidate failed, retrying.",
+			);
+
+			futures_timer::Delay::new(self.retry_delay).await;
+		}
+	}
+}
+
+/// A [`ParachainConsensus`] that falls back to a secondary consensus.
+///
+/// The `primary` consensus is asked for a candidate first. Only if it returns `None`, the
+/// `secondary` consensus is asked. This allows to run two consensus implementations side by side,
+/// e.g. while migrating from one to the other.
+#[derive(Clone)]
+pub struct FallbackConsensus<P, S> {
+	/// The consensus that is asked first.
+	pub primary: P,
+	/// The consensus that is asked when `primary` did not produce a candidate.
+	pub secondary: S,
+}
+
+#[async_trait::async_trait]
+impl<B, P, S> ParachainConsensus<B> for FallbackConsensus<P, S>
+where
+	B: BlockT,
+	P: ParachainConsensus<B> + Clone,
+	S: ParachainConsensus<B> + Clone,
+{
+	async fn produce_candidate(
+		&mut self,
+		parent: &B::Header,
+		relay_parent: PHash,
+		validation_data: &PersistedValidationData,
+	) -> Option<ParachainCandidate<B>> {
+		if let Some(candidate) = self
+			.primary
+			.produce_candidate(parent, relay_parent, validation_data)
+			.await
+		{
+			return Some(candidate);
+		}
+
+		tracing::debug!(
+			target: "cumulus-consensus",
+			?relay_parent,
+			"Primary consensus did not produce a candidate, falling back to secondary.",
+		);
+
+		self.secondary
+			.produce_candidate(parent, relay_parent, validation_data)
+			.await
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -572,7 +703,7 @@ mod tests {
 	};
 	use futures::{channel::mpsc, executor::block_on};
 	use futures_timer::Delay;
-	use std::{sync::Mutex, time::Duration};
+	use std::sync::Mutex;
 
 	struct RelaychainInner {
 		new_best_heads: Option<mpsc::UnboundedReceiver<Header>>,
@@ -886,4 +1017,144 @@ mod tests {
 			}
 		});
 	}
+
+	/// A consensus that fails to produce a candidate the first `failures` times it is called.
+	#[derive(Clone)]
+	struct FailingConsensus {
+		block: Block,
+		failures: u32,
+		calls: Arc<Mutex<u32>>,
+	}
+
+	impl FailingConsensus {
+		fn new(client: &Client, failures: u32) -> Self {
+			let block = client
+				.init_block_builder(None, Default::default())
+				.build()
+				.unwrap()
+				.block;
+
+			Self {
+				block,
+				failures,
+				calls: Arc::new(Mutex::new(0)),
+			}
+		}
+
+		fn calls(&self) -> u32 {
+			*self.calls.lock().unwrap()
+		}
+	}
+
+	#[async_trait::async_trait]
+	impl ParachainConsensus<Block> for FailingConsensus {
+		async fn produce_candidate(
+			&mut self,
+			_: &Header,
+			_: PHash,
+			_: &PersistedValidationData,
+		) -> Option<ParachainCandidate<Block>> {
+			let mut calls = self.calls.lock().unwrap();
+			*calls += 1;
+
+			if *calls <= self.failures {
+				None
+			} else {
+				Some(ParachainCandidate {
+					block: self.block.clone(),
+					proof: sp_trie::StorageProof::empty(),
+				})
+			}
+		}
+	}
+
+	#[test]
+	fn retrying_consensus_returns_candidate_after_failure() {
+		let client = TestClientBuilder::default().build();
+		let inner = FailingConsensus::new(&client, 1);
+		let header = inner.block.header().clone();
+
+		let mut consensus = RetryingConsensus::new(
+			inner.clone(),
+			2,
+			Duration::from_millis(10),
+			Duration::from_secs(10),
+		);
+		let candidate = block_on(consensus.produce_candidate(
+			&header,
+			Default::default(),
+			&Default::default(),
+		))
+		.expect("The second attempt produces a candidate");
+
+		assert_eq!(inner.block.hash(), candidate.block.hash());
+		assert_eq!(2, inner.calls());
+	}
+
+	#[test]
+	fn retrying_consensus_gives_up_after_max_retries() {
+		let client = TestClientBuilder::default().build();
+		let inner = FailingConsensus::new(&client, 5);
+		let header = inner.block.header().clone();
+
+		let mut consensus = RetryingConsensus::new(
+			inner.clone(),
+			2,
+			Duration::from_millis(10),
+			Duration::from_secs(10),
+		);
+		let candidate = block_on(consensus.produce_candidate(
+			&header,
+			Default::default(),
+			&Default::default(),
+		));
+
+		assert!(candidate.is_none());
+		assert_eq!(3, inner.calls());
+	}
+
+	#[test]
+	fn retrying_consensus_stops_at_deadline() {
+		let client = TestClientBuilder::default().build();
+		let inner = FailingConsensus::new(&client, 5);
+		let header = inner.block.header().clone();
+
+		let mut consensus = RetryingConsensus::new(
+			inner.clone(),
+			10,
+			Duration::from_millis(10),
+			Duration::from_millis(25),
+		);
+		let candidate = block_on(consensus.produce_candidate(
+			&header,
+			Default::default(),
+			&Default::default(),
+		));
+
+		assert!(candidate.is_none());
+		assert!(inner.calls() <= 3);
+	}
+
+	#[test]
+	fn fallback_consensus_uses_secondary_if_primary_fails() {
+		let client = TestClientBuilder::default().build();
+		let primary = FailingConsensus::new(&client, u32::max_value());
+		let secondary = FailingConsensus::new(&client, 0);
+		let header = secondary.block.header().clone();
+
+		let mut consensus = FallbackConsensus {
+			primary: primary.clone(),
+			secondary: secondary.clone(),
+		};
+		let candidate = block_on(consensus.produce_candidate(
+			&header,
+			Default::default(),
+			&Default::default(),
+		))
+		.expect("The secondary consensus produces a candidate");
+
+		assert_eq!(secondary.block.hash(), candidate.block.hash());
+		assert_eq!(1, primary.calls());
+		assert_eq!(1, secondary.calls());
+	}
 }
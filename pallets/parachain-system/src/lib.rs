@@ -269,6 +269,15 @@ decl_module! {
 			Ok(Pays::No.into())
 		}
 
+		/// Set the weight reserved at the beginning of the block for processing XCMP messages.
+		///
+		/// This overrides `Config::ReservedXcmpWeight`. Passing `None` removes the override.
+		#[weight = (1_000, DispatchClass::Operational)]
+		fn set_reserved_xcmp_weight_override(origin, weight: Option<Weight>) {
+			ensure_root(origin)?;
+			ReservedXcmpWeightOverride::set(weight);
+		}
+
 		fn on_finalize() {
 			DidSetValidationCode::kill();
 
@@ -935,6 +944,7 @@ mod tests {
 		static HANDLED_DOWNWARD_MESSAGES: RefCell<Vec<InboundDownwardMessage>> = RefCell::new(Vec::new());
 		static HANDLED_XCMP_MESSAGES: RefCell<Vec<(ParaId, relay_chain::BlockNumber, Vec<u8>)>> = RefCell::new(Vec::new());
 		static SENT_MESSAGES: RefCell<Vec<(ParaId, Vec<u8>)>> = RefCell::new(Vec::new());
+		static LAST_XCMP_MAX_WEIGHT: RefCell<Option<Weight>> = RefCell::new(None);
 	}
 
 	fn send_message(
@@ -979,8 +989,9 @@ mod tests {
 	impl XcmpMessageHandler for SaveIntoThreadLocal {
 		fn handle_xcmp_messages<'a, I: Iterator<Item=(ParaId, RelayBlockNumber, &'a [u8])>>(
 			iter: I,
-			_max_weight: Weight,
+			max_weight: Weight,
 		) -> Weight {
+			LAST_XCMP_MAX_WEIGHT.with(|w| *w.borrow_mut() = Some(max_weight));
 			HANDLED_XCMP_MESSAGES.with(|m| {
 				for (sender, sent_at, message) in iter {
 					m.borrow_mut().push((sender, sent_at, message.to_vec()));
@@ -995,6 +1006,7 @@ mod tests {
 	fn new_test_ext() -> sp_io::TestExternalities {
 		HANDLED_DOWNWARD_MESSAGES.with(|m| m.borrow_mut().clear());
 		HANDLED_XCMP_MESSAGES.with(|m| m.borrow_mut().clear());
+		LAST_XCMP_MAX_WEIGHT.with(|w| *w.borrow_mut() = None);
 
 		frame_system::GenesisConfig::default()
 			.build_storage::<Test>()
@@ -1260,6 +1272,35 @@ mod tests {
 			);
 	}
 
+	#[test]
+	fn reserved_xcmp_weight_can_be_overridden() {
+		BlockTests::new()
+			.add(123, || {
+				assert_eq!(LAST_XCMP_MAX_WEIGHT.with(|w| *w.borrow()), Some(0));
+				assert_eq!(
+					ParachainSystem::set_reserved_xcmp_weight_override(
+						RawOrigin::None.into(),
+						Some(5_000),
+					),
+					Err(DispatchError::BadOrigin),
+				);
+				assert_ok!(ParachainSystem::set_reserved_xcmp_weight_override(
+					RawOrigin::Root.into(),
+					Some(5_000),
+				));
+			})
+			.add(124, || {
+				assert_eq!(LAST_XCMP_MAX_WEIGHT.with(|w| *w.borrow()), Some(5_000));
+				assert_ok!(ParachainSystem::set_reserved_xcmp_weight_override(
+					RawOrigin::Root.into(),
+					None,
+				));
+			})
+			.add(125, || {
+				assert_eq!(LAST_XCMP_MAX_WEIGHT.with(|w| *w.borrow()), Some(0));
+			});
+	}
+
 	#[test]
 	fn non_overlapping() {
 		BlockTests::new()
@@ -20,7 +20,10 @@
 
 use frame_support::{decl_module, decl_storage, traits::Get};
 
-use cumulus_primitives_core::ParaId;
+use cumulus_primitives_core::{
+	relay_chain::BlockNumber as RelayChainBlockNumber, OnValidationData, ParaId,
+	PersistedValidationData,
+};
 
 /// Configuration trait of this pallet.
 pub trait Config: frame_system::Config {}
@@ -31,12 +34,95 @@ impl<T: Config> Get<ParaId> for Module<T> {
 	}
 }
 
+impl<T: Config> OnValidationData for Module<T> {
+	fn on_validation_data(data: &PersistedValidationData) {
+		RelayParentNumber::put(data.relay_parent_number);
+	}
+}
+
 decl_storage! {
 	trait Store for Module<T: Config> as ParachainInfo {
 		ParachainId get(fn parachain_id) config(): ParaId = 100.into();
+		/// The relay chain block number this parachain block was built on.
+		///
+		/// Updated by the `OnValidationData` hook of the parachain system pallet.
+		RelayParentNumber get(fn relay_parent_number): RelayChainBlockNumber;
 	}
 }
 
 decl_module! {
 	pub struct Module<T: Config> for enum Call where origin: T::Origin {}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use frame_support::parameter_types;
+	use sp_core::H256;
+	use sp_runtime::{
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+	};
+
+	use crate as parachain_info;
+
+	type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+	type Block = frame_system::mocking::MockBlock<Test>;
+
+	frame_support::construct_runtime!(
+		pub enum Test where
+			Block = Block,
+			NodeBlock = Block,
+			UncheckedExtrinsic = UncheckedExtrinsic,
+		{
+			System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+			ParachainInfo: parachain_info::{Pallet, Storage},
+		}
+	);
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+	}
+	impl frame_system::Config for Test {
+		type Origin = Origin;
+		type Call = Call;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = Event;
+		type BlockHashCount = BlockHashCount;
+		type BlockLength = ();
+		type BlockWeights = ();
+		type Version = ();
+		type PalletInfo = PalletInfo;
+		type AccountData = ();
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type DbWeight = ();
+		type BaseCallFilter = ();
+		type SystemWeightInfo = ();
+		type SS58Prefix = ();
+		type OnSetCode = ();
+	}
+	impl Config for Test {}
+
+	#[test]
+	fn on_validation_data_stores_relay_parent_number() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			assert_eq!(ParachainInfo::relay_parent_number(), 0);
+
+			for relay_parent_number in [123, 124].iter() {
+				<ParachainInfo as OnValidationData>::on_validation_data(&PersistedValidationData {
+					relay_parent_number: *relay_parent_number,
+					..Default::default()
+				});
+				assert_eq!(ParachainInfo::relay_parent_number(), *relay_parent_number);
+			}
+		});
+	}
+}
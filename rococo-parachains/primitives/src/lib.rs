@@ -18,11 +18,13 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::Codec;
 use sp_runtime::{
 	generic,
 	traits::{BlakeTwo256, IdentifyAccount, Verify},
 	MultiSignature,
 };
+use xcm::VersionedXcm;
 
 pub use sp_runtime::OpaqueExtrinsic as UncheckedExtrinsic;
 
@@ -57,3 +59,14 @@ pub type Hash = sp_core::H256;
 
 /// Digest item type.
 pub type DigestItem = generic::DigestItem<Hash>;
+
+sp_api::decl_runtime_apis! {
+	/// The API to estimate the weight of XCM messages before sending them.
+	pub trait XcmWeightApi<Call> where Call: Codec {
+		/// Returns the weight the XCM executor would reserve for executing `message`.
+		///
+		/// Returns `None` if the message can not be converted to a supported XCM version or if
+		/// it can not be weighed.
+		fn query_xcm_weight(message: VersionedXcm<Call>) -> Option<u64>;
+	}
+}
@@ -27,11 +27,11 @@ use sp_api::impl_runtime_apis;
 use sp_core::OpaqueMetadata;
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{BlakeTwo256, Block as BlockT, IdentityLookup},
+	traits::{BlakeTwo256, Block as BlockT, ConvertInto, IdentityLookup, OpaqueKeys},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult,
 };
-use sp_std::prelude::*;
+use sp_std::{convert::TryFrom, prelude::*};
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
@@ -46,29 +46,38 @@ pub use frame_support::{
 	},
 	StorageValue,
 };
-use frame_system::limits::{BlockLength, BlockWeights};
+use frame_support::{traits::Contains, weights::WeightToFeePolynomial, PalletId};
+use frame_system::{
+	limits::{BlockLength, BlockWeights},
+	EnsureRoot,
+};
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
+pub use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
 
 // XCM imports
 use polkadot_parachain::primitives::Sibling;
-use xcm::v0::{Junction, MultiLocation, NetworkId};
+use xcm::v0::{Junction, MultiAsset, MultiLocation, NetworkId, OriginKind, Xcm};
+use xcm::VersionedXcm;
 use xcm_builder::{
 	AccountId32Aliases, CurrencyAdapter, LocationInverter, ParentIsDefault, RelayChainAsNative,
 	SiblingParachainAsNative, SiblingParachainConvertsVia, SignedAccountId32AsNative,
 	SovereignSignedViaLocation, FixedRateOfConcreteFungible, EnsureXcmOrigin,
 	AllowTopLevelPaidExecutionFrom, TakeWeightCredit, FixedWeightBounds, IsConcrete, NativeAsset,
-	AllowUnpaidExecutionFrom, ParentAsSuperuser,
+	AllowUnpaidExecutionFrom, ParentAsSuperuser, SignedToAccountId32, TakeRevenue, Case,
+};
+use xcm_executor::{
+	traits::{Convert, ConvertOrigin, TransactAsset, WeightBounds},
+	Config, XcmExecutor,
 };
-use xcm_executor::{Config, XcmExecutor};
-
-pub type SessionHandlers = ();
 
 impl_opaque_keys! {
-	pub struct SessionKeys {}
+	pub struct SessionKeys {
+		pub aura: Aura,
+	}
 }
 
 /// This runtime version.
@@ -76,7 +85,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("cumulus-test-parachain"),
 	impl_name: create_runtime_str!("cumulus-test-parachain"),
 	authoring_version: 1,
-	spec_version: 18,
+	spec_version: 19,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -93,6 +102,19 @@ pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+// Unit = the base number of indivisible units for balances
+pub const UNIT: Balance = 1_000_000_000_000;
+pub const MILLIUNIT: Balance = 1_000_000_000;
+
+/// The deposit for storing `items` items taking up `bytes` bytes in total.
+///
+/// Saturates at `Balance::MAX` instead of wrapping.
+pub const fn deposit(items: u32, bytes: u32) -> Balance {
+	(items as Balance)
+		.saturating_mul(UNIT)
+		.saturating_add((bytes as Balance).saturating_mul(10 * MILLIUNIT))
+}
+
 // 1 in 4 blocks (on average, not counting collisions) will be primary babe blocks.
 pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
 
@@ -218,7 +240,7 @@ impl pallet_balances::Config for Runtime {
 }
 
 impl pallet_transaction_payment::Config for Runtime {
-	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, Treasury>;
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = IdentityFee<Balance>;
 	type FeeMultiplierUpdate = ();
@@ -229,13 +251,58 @@ impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = UNIT;
+	pub const SpendPeriod: BlockNumber = DAYS;
+	// Nothing is burned, the treasury keeps all the fees it collects.
+	pub const Burn: Permill = Permill::zero();
+	pub const MaxApprovals: u32 = 100;
+}
+
+impl pallet_treasury::Config for Runtime {
+	type PalletId = TreasuryPalletId;
+	type Currency = Balances;
+	type ApproveOrigin = EnsureRoot<AccountId>;
+	type RejectOrigin = EnsureRoot<AccountId>;
+	type Event = Event;
+	type OnSlash = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type SpendPeriod = SpendPeriod;
+	type Burn = Burn;
+	type BurnDestination = ();
+	type SpendFunds = ();
+	type WeightInfo = ();
+	type MaxApprovals = MaxApprovals;
+}
+
+parameter_types! {
+	// One storage item; key size is 32; value is size 4+4+16+32 bytes = 56 bytes.
+	pub const DepositBase: Balance = deposit(1, 88);
+	// Additional storage item size of 32 bytes.
+	pub const DepositFactor: Balance = deposit(0, 32);
+	pub const MaxSignatories: u16 = 100;
+}
+
+impl pallet_multisig::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type DepositBase = DepositBase;
+	type DepositFactor = DepositFactor;
+	type MaxSignatories = MaxSignatories;
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub const ReservedXcmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
 }
 
 impl cumulus_pallet_parachain_system::Config for Runtime {
 	type Event = Event;
-	type OnValidationData = ();
+	type OnValidationData = ParachainInfo;
 	type SelfParaId = parachain_info::Module<Runtime>;
 	type DownwardMessageHandlers = cumulus_primitives_utility::UnqueuedDmpAsParent<
 		MaxDownwardMessageWeight,
@@ -249,6 +316,31 @@ impl cumulus_pallet_parachain_system::Config for Runtime {
 
 impl parachain_info::Config for Runtime {}
 
+parameter_types! {
+	pub const Period: BlockNumber = 6 * HOURS;
+	pub const Offset: BlockNumber = 0;
+	pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(33);
+}
+
+impl pallet_session::Config for Runtime {
+	type Event = Event;
+	type ValidatorId = <Self as frame_system::Config>::AccountId;
+	// We don't have stash and controller accounts, so collators are identified by their account.
+	type ValidatorIdOf = ConvertInto;
+	type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
+	// The collator set is only ever the one given at genesis for now.
+	type SessionManager = ();
+	type SessionHandler = <SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = SessionKeys;
+	type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
+	type WeightInfo = ();
+}
+
+impl pallet_aura::Config for Runtime {
+	type AuthorityId = AuraId;
+}
+
 parameter_types! {
 	pub const RococoLocation: MultiLocation = MultiLocation::X1(Junction::Parent);
 	pub const RococoNetwork: NetworkId = NetworkId::Polkadot;
@@ -282,10 +374,37 @@ pub type LocalAssetTransactor = CurrencyAdapter<
 	AccountId,
 >;
 
+/// Converts an XCM origin with `Converter`, unless the `(OriginKind, MultiLocation)` pair is
+/// contained in `Denied`.
+///
+/// A denied origin is handed back unconverted without consulting `Converter` at all. When wrapping
+/// a whole tuple of converters, as done below, the conversion and with it the `Transact` fails.
+pub struct FilteredOriginConverter<Converter, Denied>(sp_std::marker::PhantomData<(Converter, Denied)>);
+impl<O, Converter, Denied> ConvertOrigin<O> for FilteredOriginConverter<Converter, Denied>
+where
+	Converter: ConvertOrigin<O>,
+	Denied: Contains<(OriginKind, MultiLocation)>,
+{
+	fn convert_origin(origin: MultiLocation, kind: OriginKind) -> Result<O, MultiLocation> {
+		if Denied::contains(&(kind, origin.clone())) {
+			return Err(origin);
+		}
+		Converter::convert_origin(origin, kind)
+	}
+}
+
+parameter_types! {
+	// The `(OriginKind, MultiLocation)` pairs that may not be used to dispatch a `Transact`. This
+	// is a `storage` parameter, so root can change it by writing the SCALE encoded list under the
+	// key `twox_128(b":DeniedTransactOrigins:")` through `System::set_storage`, e.g. to deny
+	// `(OriginKind::Superuser, X1(Parent))` on a production chain. Nothing is denied by default.
+	pub storage DeniedTransactOrigins: Vec<(OriginKind, MultiLocation)> = Vec::new();
+}
+
 /// This is the type we use to convert an (incoming) XCM origin into a local `Origin` instance,
 /// ready for dispatching a transaction with Xcm's `Transact`. There is an `OriginKind` which can
 /// biases the kind of local `Origin` it will become.
-pub type XcmOriginToTransactDispatchOrigin = (
+pub type XcmOriginToTransactDispatchOrigin = FilteredOriginConverter<(
 	// Sovereign account converter; this attempts to derive an `AccountId` from the origin location
 	// using `LocationToAccountId` and then turn that into the usual `Signed` origin. Useful for
 	// foreign chains who want to have a local sovereign account on this chain which they control.
@@ -302,25 +421,50 @@ pub type XcmOriginToTransactDispatchOrigin = (
 	// Native signed account converter; this just converts an `AccountId32` origin into a normal
 	// `Origin::Signed` origin of the same 32-byte value.
 	SignedAccountId32AsNative<RococoNetwork, Origin>,
-);
+), IsInVec<DeniedTransactOrigins>>;
 
 parameter_types! {
 	pub UnitWeightCost: Weight = 1_000;
 }
 
 parameter_types! {
-	// 1_000_000_000_000 => 1 unit of asset for 1 unit of Weight.
-	// TODO: Should take the actual weight price. This is just 1_000 ROC per second of weight.
-	pub const WeightPrice: (MultiLocation, u128) = (MultiLocation::X1(Junction::Parent), 1_000);
+	// The amount of the relay-chain asset charged per second of XCM weight. This is priced the same
+	// as a local extrinsic of equal weight, i.e. through the transaction payment `WeightToFee`.
+	pub WeightPrice: (MultiLocation, u128) = (
+		MultiLocation::X1(Junction::Parent),
+		<Runtime as pallet_transaction_payment::Config>::WeightToFee::calc(&WEIGHT_PER_SECOND),
+	);
 	pub AllowUnpaidFrom: Vec<MultiLocation> = vec![ MultiLocation::X1(Junction::Parent) ];
 }
 
+/// Deposits the fees charged for XCM execution by the `Trader` into the treasury account.
+pub struct XcmFeesToTreasury;
+impl TakeRevenue for XcmFeesToTreasury {
+	fn take_revenue(revenue: MultiAsset) {
+		let treasury: MultiLocation = Junction::AccountId32 {
+			network: RococoNetwork::get(),
+			id: Treasury::account_id().into(),
+		}.into();
+		if let Err(e) = LocalAssetTransactor::deposit_asset(&revenue, &treasury) {
+			log::warn!("Failed to deposit XCM fees {:?} into the treasury: {:?}", revenue, e);
+		}
+	}
+}
+
 pub type Barrier = (
 	TakeWeightCredit,
 	AllowTopLevelPaidExecutionFrom<All<MultiLocation>>,
 	AllowUnpaidExecutionFrom<IsInVec<AllowUnpaidFrom>>,	// <- Parent gets free execution
 );
 
+parameter_types! {
+	// The relay-chain asset, when it comes from the relay chain.
+	pub const RelayAssetFromRelay: (MultiAsset, MultiLocation) = (
+		MultiAsset::AllConcreteFungible { id: MultiLocation::X1(Junction::Parent) },
+		MultiLocation::X1(Junction::Parent),
+	);
+}
+
 pub struct XcmConfig;
 impl Config for XcmConfig {
 	type Call = Call;
@@ -328,21 +472,49 @@ impl Config for XcmConfig {
 	// How to withdraw and deposit an asset.
 	type AssetTransactor = LocalAssetTransactor;
 	type OriginConverter = XcmOriginToTransactDispatchOrigin;
+	// Any chain is the reserve of its own native asset.
 	type IsReserve = NativeAsset;
-	type IsTeleporter = NativeAsset;	// <- should be enough to allow teleportation of ROC
+	// Only the relay chain is trusted to teleport, and only its own asset (ROC).
+	type IsTeleporter = Case<RelayAssetFromRelay>;
 	type LocationInverter = LocationInverter<Ancestry>;
 	type Barrier = Barrier;
 	type Weigher = FixedWeightBounds<UnitWeightCost, Call>;
-	type Trader = FixedRateOfConcreteFungible<WeightPrice, ()>;
+	type Trader = FixedRateOfConcreteFungible<WeightPrice, XcmFeesToTreasury>;
 	type ResponseHandler = ();	// Don't handle responses for now.
 }
 
 parameter_types! {
-	pub const MaxDownwardMessageWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 10;
+	// The maximum weight a single downward message may use. This is a `storage` parameter, so root
+	// can change it by writing the SCALE encoded weight under the key
+	// `twox_128(b":MaxDownwardMessageWeight:")` through `System::set_storage`. The value given here
+	// is used until then.
+	pub storage MaxDownwardMessageWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 10;
+}
+
+/// Converts the local `Root` origin into the `Null` location, i.e. this chain itself.
+pub struct RootAsNull;
+impl Convert<Origin, MultiLocation> for RootAsNull {
+	fn convert(origin: Origin) -> Result<MultiLocation, Origin> {
+		frame_system::ensure_root(origin.clone())
+			.map(|_| MultiLocation::Null)
+			.map_err(|_| origin)
+	}
 }
 
-/// No local origins on this chain are allowed to dispatch XCM sends/executions.
-pub type LocalOriginToLocation = ();
+/// Converts a local signed origin into an XCM multilocation. Signed accounts on this chain are
+/// allowed to execute XCM as themselves, e.g. to reserve transfer or teleport their own assets.
+pub type LocalOriginToLocation = (
+	// A signed origin is converted to the `AccountId32` location of the same account.
+	SignedToAccountId32<Origin, AccountId, RococoNetwork>,
+);
+
+/// Converts a local origin that may send XCM into an XCM multilocation. In addition to signed
+/// accounts, root may send arbitrary XCM as the chain itself.
+pub type LocalSendOriginToLocation = (
+	LocalOriginToLocation,
+	// Root is converted to the `Null` location.
+	RootAsNull,
+);
 
 /// The means for routing XCM messages which are not for local execution into the right message
 /// queues.
@@ -355,7 +527,7 @@ pub type XcmRouter = (
 
 impl pallet_xcm::Config for Runtime {
 	type Event = Event;
-	type SendXcmOrigin = EnsureXcmOrigin<Origin, LocalOriginToLocation>;
+	type SendXcmOrigin = EnsureXcmOrigin<Origin, LocalSendOriginToLocation>;
 	type XcmRouter = XcmRouter;
 	type ExecuteXcmOrigin = EnsureXcmOrigin<Origin, LocalOriginToLocation>;
 	type XcmExecutor = XcmExecutor<XcmConfig>;
@@ -396,6 +568,15 @@ construct_runtime! {
 		PolkadotXcm: pallet_xcm::{Pallet, Call, Event<T>, Origin},
 		CumulusXcm: cumulus_pallet_xcm::{Pallet, Origin},
 
+		// New pallets are appended here, so the indices of the pallets above do not change.
+		Treasury: pallet_treasury::{Pallet, Call, Storage, Config, Event<T>},
+
+		// Collator support. The order of these matters, `Session` needs to set up `Aura`.
+		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
+		Aura: pallet_aura::{Pallet, Config<T>},
+
+		Multisig: pallet_multisig::{Pallet, Call, Storage, Event<T>},
+
 		Spambot: cumulus_ping::{Pallet, Call, Storage, Event<T>} = 99,
 	}
 }
@@ -503,6 +684,30 @@ impl_runtime_apis! {
 			SessionKeys::generate(seed)
 		}
 	}
+
+	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
+		fn slot_duration() -> sp_consensus_aura::SlotDuration {
+			sp_consensus_aura::SlotDuration::from_millis(Aura::slot_duration())
+		}
+
+		fn authorities() -> Vec<AuraId> {
+			Aura::authorities()
+		}
+	}
+
+	impl rococo_parachain_primitives::XcmWeightApi<Block, Call> for Runtime {
+		fn query_xcm_weight(message: VersionedXcm<Call>) -> Option<Weight> {
+			type Weigher = <XcmConfig as Config>::Weigher;
+
+			let mut message = Xcm::<Call>::try_from(message).ok()?;
+			let shallow = Weigher::shallow(&mut message).ok()?;
+			let deep = Weigher::deep(&mut message).ok()?;
+			shallow.checked_add(deep)
+		}
+	}
 }
 
 cumulus_pallet_parachain_system::register_validate_block!(Runtime, Executive);
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,385 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+
+use cumulus_primitives_core::{relay_chain, AbridgedHrmpChannel, ParaId, XcmpMessageSource};
+use frame_support::{assert_noop, assert_ok, dispatch::DispatchError, traits::Currency};
+use sp_core::crypto::key_types;
+use sp_io::hashing::twox_128;
+use sp_keystore::{testing::KeyStore, KeystoreExt};
+use sp_runtime::traits::AccountIdConversion;
+use std::sync::Arc;
+use xcm::v0::{ExecuteXcm, Order, Outcome};
+use xcm_executor::{traits::WeightTrader, AssetId, Assets};
+
+const ALICE: [u8; 32] = [1u8; 32];
+const BOB: [u8; 32] = [2u8; 32];
+const SIBLING: u32 = 2000;
+const INITIAL_BALANCE: Balance = 1_000 * UNIT;
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let alice = AccountId::from(ALICE);
+	let aura = AuraId::from(sp_core::sr25519::Public::from_raw(ALICE));
+
+	let storage = GenesisConfig {
+		frame_system: Default::default(),
+		pallet_balances: BalancesConfig {
+			balances: vec![(alice.clone(), INITIAL_BALANCE)],
+		},
+		pallet_sudo: SudoConfig { key: alice.clone() },
+		pallet_treasury: Default::default(),
+		parachain_info: ParachainInfoConfig {
+			parachain_id: 100.into(),
+		},
+		pallet_session: SessionConfig {
+			keys: vec![(alice.clone(), alice, SessionKeys { aura })],
+		},
+		pallet_aura: AuraConfig {
+			authorities: Default::default(),
+		},
+	}
+	.build_storage()
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+fn account_location(id: [u8; 32]) -> MultiLocation {
+	Junction::AccountId32 {
+		network: NetworkId::Any,
+		id,
+	}
+	.into()
+}
+
+fn relay_asset(amount: Balance) -> MultiAsset {
+	MultiAsset::ConcreteFungible {
+		id: MultiLocation::X1(Junction::Parent),
+		amount,
+	}
+}
+
+/// The fee the XCM `Trader` charges for buying `weight`.
+fn execution_fee(weight: Weight) -> Balance {
+	WeightPrice::get().1 * weight as Balance / WEIGHT_PER_SECOND as Balance
+}
+
+/// Withdraws `amount` of the relay chain asset, buys `debt` weight with it and deposits the rest
+/// to Bob.
+fn pay_and_deposit_to_bob(amount: Balance, debt: Weight) -> Xcm<Call> {
+	Xcm::WithdrawAsset {
+		assets: vec![relay_asset(amount)],
+		effects: vec![
+			Order::BuyExecution {
+				fees: MultiAsset::All,
+				weight: 0,
+				debt,
+				halt_on_error: true,
+				xcm: vec![],
+			},
+			Order::DepositAsset {
+				assets: vec![MultiAsset::All],
+				dest: account_location(BOB),
+			},
+		],
+	}
+}
+
+/// The shallow plus deep weight `FixedWeightBounds` gives `message`.
+fn fixed_bounds_weight(mut message: Xcm<Call>) -> Weight {
+	type Bounds = FixedWeightBounds<UnitWeightCost, Call>;
+
+	let shallow = Bounds::shallow(&mut message).expect("message can be weighed");
+	let deep = Bounds::deep(&mut message).expect("message can be weighed");
+	shallow + deep
+}
+
+/// Opens an outbound HRMP channel to `recipient`.
+///
+/// The parachain system pallet learns about channels from the relay chain state in the validation
+/// data inherent. This writes the resulting `RelevantMessagingState` directly. The tuple has the
+/// same encoding as `MessagingStateSnapshot`, which is private to that pallet.
+fn open_hrmp_channel(recipient: u32) {
+	let messaging_state = (
+		relay_chain::Hash::default(),
+		(0u32, 0u32),
+		Vec::<(ParaId, AbridgedHrmpChannel)>::new(),
+		vec![(
+			ParaId::from(recipient),
+			AbridgedHrmpChannel {
+				max_capacity: 10,
+				max_total_size: 64 * 1024,
+				max_message_size: 32 * 1024,
+				msg_count: 0,
+				total_size: 0,
+				mqc_head: None,
+			},
+		)],
+	);
+	let key = [
+		twox_128(b"ParachainSystem"),
+		twox_128(b"RelevantMessagingState"),
+	]
+	.concat();
+	frame_support::storage::unhashed::put(&key, &messaging_state);
+}
+
+fn convert_transact_origin(
+	location: MultiLocation,
+	kind: OriginKind,
+) -> Result<Origin, MultiLocation> {
+	<XcmOriginToTransactDispatchOrigin as ConvertOrigin<Origin>>::convert_origin(location, kind)
+}
+
+#[test]
+fn session_keys_decode_into_the_generated_aura_key() {
+	let mut ext = sp_io::TestExternalities::default();
+	ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
+
+	ext.execute_with(|| {
+		let encoded = SessionKeys::generate(None);
+
+		let aura_keys = sp_io::crypto::sr25519_public_keys(key_types::AURA);
+		assert_eq!(aura_keys.len(), 1);
+		assert_eq!(
+			SessionKeys::decode_into_raw_public_keys(&encoded),
+			Some(vec![(aura_keys[0].0.to_vec(), key_types::AURA)]),
+		);
+	});
+}
+
+#[test]
+fn aura_slot_duration_matches_slot_duration() {
+	// `AuraApi::slot_duration` reports `Aura::slot_duration()`, which is derived from the timestamp
+	// pallet's `MinimumPeriod`. Any client authoring with Aura must see `SLOT_DURATION`.
+	assert_eq!(Aura::slot_duration(), SLOT_DURATION);
+}
+
+#[test]
+fn denied_superuser_transact_origin_is_not_converted() {
+	new_test_ext().execute_with(|| {
+		let parent = MultiLocation::X1(Junction::Parent);
+		let origin = convert_transact_origin(parent.clone(), OriginKind::Superuser)
+			.ok()
+			.expect("the relay chain is a superuser by default");
+		assert!(frame_system::ensure_root(origin).is_ok());
+
+		DeniedTransactOrigins::set(&vec![(OriginKind::Superuser, parent.clone())]);
+		assert_eq!(
+			convert_transact_origin(parent.clone(), OriginKind::Superuser).err(),
+			Some(parent),
+		);
+	});
+}
+
+#[test]
+fn signed_transact_origin_is_converted_while_superuser_is_denied() {
+	new_test_ext().execute_with(|| {
+		DeniedTransactOrigins::set(&vec![(
+			OriginKind::Superuser,
+			MultiLocation::X1(Junction::Parent),
+		)]);
+
+		let alice: MultiLocation = Junction::AccountId32 {
+			network: RococoNetwork::get(),
+			id: ALICE,
+		}
+		.into();
+		let origin = convert_transact_origin(alice, OriginKind::Native)
+			.ok()
+			.expect("a signed origin is not denied");
+		let who: Result<AccountId, _> = frame_system::ensure_signed(origin);
+		assert_eq!(who, Ok(AccountId::from(ALICE)));
+	});
+}
+
+#[test]
+fn only_signed_accounts_execute_xcm_and_only_with_their_own_assets() {
+	new_test_ext().execute_with(|| {
+		let debt = 1_000_000;
+		let message = pay_and_deposit_to_bob(UNIT, debt);
+
+		assert_ok!(PolkadotXcm::execute(
+			Origin::signed(AccountId::from(ALICE)),
+			Box::new(message.clone()),
+			1_000_000_000,
+		));
+		assert_eq!(
+			Balances::free_balance(AccountId::from(ALICE)),
+			INITIAL_BALANCE - UNIT,
+		);
+		assert_eq!(
+			Balances::free_balance(AccountId::from(BOB)),
+			UNIT - execution_fee(debt),
+		);
+
+		// The withdrawal is taken from the signer's own account. Bob has nothing to withdraw
+		// from Alice.
+		assert_ok!(PolkadotXcm::execute(
+			Origin::signed(AccountId::from(BOB)),
+			Box::new(Xcm::WithdrawAsset {
+				assets: vec![relay_asset(INITIAL_BALANCE)],
+				effects: vec![],
+			}),
+			1_000_000_000,
+		));
+		assert_eq!(
+			Balances::free_balance(AccountId::from(ALICE)),
+			INITIAL_BALANCE - UNIT,
+		);
+
+		// Root may only send XCM, not execute it.
+		assert_noop!(
+			PolkadotXcm::execute(Origin::root(), Box::new(message), 1_000_000_000),
+			DispatchError::BadOrigin,
+		);
+	});
+}
+
+#[test]
+fn signed_account_reserve_transfers_to_sibling() {
+	new_test_ext().execute_with(|| {
+		open_hrmp_channel(SIBLING);
+
+		assert_ok!(PolkadotXcm::reserve_transfer_assets(
+			Origin::signed(AccountId::from(ALICE)),
+			MultiLocation::X2(Junction::Parent, Junction::Parachain(SIBLING)),
+			account_location(BOB),
+			vec![relay_asset(UNIT)],
+			1_000_000,
+		));
+
+		let sibling_account: AccountId = Sibling(SIBLING.into()).into_account();
+		assert_eq!(
+			Balances::free_balance(AccountId::from(ALICE)),
+			INITIAL_BALANCE - UNIT,
+		);
+		assert_eq!(Balances::free_balance(&sibling_account), UNIT);
+
+		// The `ReserveAssetDeposit` went through the `XcmRouter` into the XCMP queue.
+		let outbound = XcmpQueue::take_outbound_messages(usize::max_value());
+		assert_eq!(outbound.len(), 1);
+		assert_eq!(outbound[0].0, ParaId::from(SIBLING));
+	});
+}
+
+#[test]
+fn root_sends_xcm_as_the_chain_itself() {
+	new_test_ext().execute_with(|| {
+		open_hrmp_channel(SIBLING);
+
+		assert_ok!(PolkadotXcm::send(
+			Origin::root(),
+			MultiLocation::X2(Junction::Parent, Junction::Parachain(SIBLING)),
+			Xcm::WithdrawAsset {
+				assets: vec![relay_asset(UNIT)],
+				effects: vec![],
+			},
+		));
+
+		let outbound = XcmpQueue::take_outbound_messages(usize::max_value());
+		assert_eq!(outbound.len(), 1);
+		assert_eq!(outbound[0].0, ParaId::from(SIBLING));
+	});
+}
+
+#[test]
+fn xcm_execution_fees_go_to_the_treasury() {
+	new_test_ext().execute_with(|| {
+		let sibling = MultiLocation::X2(Junction::Parent, Junction::Parachain(SIBLING));
+		let sibling_account: AccountId = Sibling(SIBLING.into()).into_account();
+		Balances::make_free_balance_be(&sibling_account, 10 * UNIT);
+		let treasury_before = Balances::free_balance(Treasury::account_id());
+
+		let debt = 1_000_000;
+		let outcome = XcmExecutor::<XcmConfig>::execute_xcm(
+			sibling,
+			pay_and_deposit_to_bob(UNIT, debt),
+			1_000_000_000,
+		);
+		assert!(matches!(outcome, Outcome::Complete(_)));
+
+		let fee = execution_fee(debt);
+		assert!(fee > 0);
+		assert_eq!(
+			Balances::free_balance(Treasury::account_id()),
+			treasury_before + fee,
+		);
+		assert_eq!(Balances::free_balance(AccountId::from(BOB)), UNIT - fee);
+	});
+}
+
+#[test]
+fn query_xcm_weight_matches_the_weigher() {
+	let single = Xcm::<Call>::WithdrawAsset {
+		assets: vec![relay_asset(UNIT)],
+		effects: vec![],
+	};
+	let multi = pay_and_deposit_to_bob(UNIT, 1_000_000);
+	let query = <Runtime as runtime_decl_for_XcmWeightApi::XcmWeightApi<Block, Call>>::query_xcm_weight;
+
+	let single_weight =
+		query(VersionedXcm::from(single.clone())).expect("single instruction can be weighed");
+	let multi_weight =
+		query(VersionedXcm::from(multi.clone())).expect("multiple instructions can be weighed");
+
+	assert_eq!(single_weight, fixed_bounds_weight(single));
+	assert_eq!(multi_weight, fixed_bounds_weight(multi));
+	assert!(multi_weight > single_weight);
+}
+
+#[test]
+fn trader_charges_more_for_more_instructions() {
+	new_test_ext().execute_with(|| {
+		let charged = |message: Xcm<Call>| -> Balance {
+			let weight = fixed_bounds_weight(message);
+			let mut trader = <XcmConfig as Config>::Trader::new();
+			let unspent = trader
+				.buy_weight(weight, Assets::from(vec![relay_asset(UNIT)]))
+				.expect("payment covers the weight");
+			let relay = AssetId::Concrete(MultiLocation::X1(Junction::Parent));
+			UNIT - unspent.fungible.get(&relay).copied().unwrap_or_default()
+		};
+
+		let single = Xcm::<Call>::WithdrawAsset {
+			assets: vec![relay_asset(UNIT)],
+			effects: vec![],
+		};
+		let multi = pay_and_deposit_to_bob(UNIT, 1_000_000);
+
+		assert_eq!(charged(single.clone()), execution_fee(fixed_bounds_weight(single.clone())));
+		assert!(charged(multi) > charged(single));
+	});
+}
+
+#[test]
+fn deposit_does_not_wrap_for_large_inputs() {
+	// The relay chain's default `max_code_size`.
+	const MAX_CODE_SIZE: u32 = 2 * 1024 * 1024;
+
+	assert_eq!(
+		deposit(1, MAX_CODE_SIZE),
+		UNIT + MAX_CODE_SIZE as Balance * 10 * MILLIUNIT,
+	);
+	assert_eq!(
+		deposit(u32::MAX, u32::MAX),
+		u32::MAX as Balance * UNIT + u32::MAX as Balance * 10 * MILLIUNIT,
+	);
+	assert!(deposit(u32::MAX, u32::MAX) > deposit(1, MAX_CODE_SIZE));
+}